@@ -0,0 +1,46 @@
+use std::{cell::RefCell, rc::Rc};
+
+use async_trait::async_trait;
+use serviceless::{LocalContext, LocalHandler, LocalService, Message};
+
+#[derive(Debug, Default)]
+pub struct Counter {
+    count: Rc<RefCell<u64>>,
+}
+
+impl LocalService for Counter {}
+
+#[derive(Debug)]
+pub struct Add(pub Rc<u64>);
+
+impl Message for Add {
+    type Result = u64;
+}
+
+#[async_trait(?Send)]
+impl LocalHandler<Add> for Counter {
+    async fn handler(&mut self, message: Add, _ctx: &mut LocalContext<Self>) -> u64 {
+        let mut count = self.count.borrow_mut();
+        *count += *message.0;
+        *count
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let local = tokio::task::LocalSet::new();
+
+    local
+        .run_until(async {
+            let addr = Counter::default().start();
+
+            let res = addr.call(Add(Rc::new(2))).await.unwrap();
+
+            println!("{:?}", res);
+
+            let res = addr.call(Add(Rc::new(3))).await.unwrap();
+
+            println!("{:?}", res);
+        })
+        .await;
+}
@@ -1,27 +1,141 @@
-use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use std::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::Duration,
+};
 
-use crate::{envelop::Envelope, Error, Handler, Message, Result, Service};
+use tokio::sync::{
+    mpsc::{UnboundedSender, WeakUnboundedSender},
+    oneshot,
+};
+
+use crate::{
+    envelop::Envelope, Call, CallError, CallStack, Error, Handler, Message, Result, SendError,
+    Service, ServiceId, ServiceState,
+};
 
 /// Address of Service
 ///
 /// This address can clone.
 pub struct Address<S> {
     pub(crate) sender: UnboundedSender<Envelope<S>>,
+    state: Arc<ServiceState>,
 }
 
 impl<S> Clone for Address<S> {
     fn clone(&self) -> Self {
-        Self {
-            sender: self.sender.clone(),
-        }
+        Self::new(self.sender.clone(), self.state.clone())
+    }
+}
+
+impl<S> Debug for Address<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Address")
+            .field("service", &std::any::type_name::<S>())
+            .field("id", &self.state.id)
+            .field("stopped", &self.is_stop())
+            .finish()
+    }
+}
+
+impl<S> PartialEq for Address<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.points_to_same_service(other)
+    }
+}
+
+impl<S> Eq for Address<S> {}
+
+impl<S> Hash for Address<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.state.id.hash(state)
+    }
+}
+
+impl<S> Drop for Address<S> {
+    fn drop(&mut self) {
+        self.state.address_dropped();
     }
 }
 
 impl<S> Address<S> {
+    pub(crate) fn new(sender: UnboundedSender<Envelope<S>>, state: Arc<ServiceState>) -> Self {
+        state.address_created();
+
+        Self { sender, state }
+    }
+
     /// Return true when service stopped.
     pub fn is_stop(&self) -> bool {
         self.sender.is_closed()
     }
+
+    /// Get id of service
+    pub fn id(&self) -> ServiceId {
+        self.state.id
+    }
+
+    /// Return true if two addresses point to the same service
+    pub fn points_to_same_service(&self, other: &Address<S>) -> bool {
+        self.sender.same_channel(&other.sender)
+    }
+
+    /// Wait until service's `started` hook finished
+    ///
+    /// Return `ServiceStoped` if service stopped before started, like `init` failed.
+    pub async fn wait_ready(&self) -> Result<()> {
+        self.state.wait_ready().await
+    }
+
+    /// Create a weak address
+    pub fn downgrade(&self) -> WeakAddress<S> {
+        WeakAddress {
+            sender: self.sender.downgrade(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Weak address of Service
+///
+/// This address don't keep mailbox of service alive, so it can be used to
+/// break reference cycles between services. Use `upgrade` to get an `Address`.
+pub struct WeakAddress<S> {
+    sender: WeakUnboundedSender<Envelope<S>>,
+    state: Arc<ServiceState>,
+}
+
+impl<S> Clone for WeakAddress<S> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<S> Debug for WeakAddress<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakAddress")
+            .field("service", &std::any::type_name::<S>())
+            .field("id", &self.state.id)
+            .finish()
+    }
+}
+
+impl<S> WeakAddress<S> {
+    /// Get id of service
+    pub fn id(&self) -> ServiceId {
+        self.state.id
+    }
+
+    /// Get an `Address`, return `None` if mailbox already dropped.
+    pub fn upgrade(&self) -> Option<Address<S>> {
+        let sender = self.sender.upgrade()?;
+
+        Some(Address::new(sender, self.state.clone()))
+    }
 }
 
 impl<S> Address<S>
@@ -29,7 +143,7 @@ where
     S: Service,
 {
     /// Call service's handler and get result
-    pub async fn call<M>(&self, message: M) -> Result<M::Result>
+    pub fn call<M>(&self, message: M) -> Call<'_, S, M>
     where
         M: Message + Send + 'static,
         S: Handler<M>,
@@ -37,27 +151,154 @@ where
     {
         let (sender, receiver) = oneshot::channel();
 
-        let env = Envelope::new(message, Some(sender));
+        let env = Envelope::new(message, sender);
+
+        Call::new(self.id(), &self.sender, env, receiver)
+    }
 
-        self.sender.send(env).map_err(|_| Error::ServiceStoped)?;
+    /// Call service's handler, distinguish whether message was delivered
+    ///
+    /// `NotDelivered` means message never enqueued, it's returned and safe to
+    /// retry. `NoReply` means message enqueued but dropped without reply, it
+    /// may already be handled. `WouldDeadlock` means service is waiting for
+    /// current handler, message returned without enqueued.
+    pub async fn try_call<M>(&self, message: M) -> std::result::Result<M::Result, CallError<M>>
+    where
+        M: Message + Send + 'static,
+        S: Handler<M>,
+        M::Result: Send,
+    {
+        if CallStack::check(self.id()).is_err() {
+            return Err(CallError::WouldDeadlock(message));
+        }
 
-        receiver.await.map_err(|_| Error::ServicePaused)
+        let (sender, receiver) = oneshot::channel();
+
+        let env = Envelope::new(message, sender);
+
+        if let Err(e) = self.sender.send(env) {
+            let message = e.0.into_message().expect("Envelope must contain message");
+
+            return Err(CallError::NotDelivered(message));
+        }
+
+        receiver.await.map_err(|_| CallError::NoReply)
+    }
+
+    /// Call service's handler with many messages and get all results
+    ///
+    /// All messages are enqueued first, so service can handle them back-to-back.
+    /// Results are in the same order as messages.
+    pub async fn call_many<M>(&self, messages: Vec<M>) -> Vec<Result<M::Result>>
+    where
+        M: Message + Send + 'static,
+        S: Handler<M>,
+        M::Result: Send,
+    {
+        if CallStack::check(self.id()).is_err() {
+            return messages.iter().map(|_| Err(Error::WouldDeadlock)).collect();
+        }
+
+        let receivers: Vec<_> = messages
+            .into_iter()
+            .map(|message| {
+                let (sender, receiver) = oneshot::channel();
+
+                let env = Envelope::new(message, sender);
+
+                self.sender
+                    .send(env)
+                    .map(|_| receiver)
+                    .map_err(|_| Error::ServiceStoped)
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(receivers.len());
+
+        for receiver in receivers {
+            let res = match receiver {
+                Ok(receiver) => receiver.await.map_err(|_| Error::Canceled),
+                Err(e) => Err(e),
+            };
+
+            results.push(res);
+        }
+
+        results
+    }
+
+    /// Call service's handler and get result, return `Timeout` if not finished in time
+    ///
+    /// Handler can read deadline of this call by `Context::deadline`. If
+    /// timeout is too large, like `Duration::MAX`, there is no deadline.
+    pub async fn call_timeout<M>(&self, message: M, timeout: Duration) -> Result<M::Result>
+    where
+        M: Message + Send + 'static,
+        S: Handler<M>,
+        M::Result: Send,
+    {
+        let timer = self.state.timer();
+
+        let (sender, receiver) = oneshot::channel();
+
+        let env = Envelope::with_deadline(message, sender, timer.now().checked_add(timeout));
+
+        tokio::select! {
+            res = Call::<S, M>::new(self.id(), &self.sender, env, receiver) => res,
+            _ = timer.sleep(timeout) => Err(Error::Timeout),
+        }
     }
 
     /// Call service's handler without result
     ///
     /// Beacuse this function don't need result, so it can call without async.
-    /// If service paused, we have no ServicePaused return.
+    /// If service paused, no error returned.
     pub fn send<M>(&self, message: M) -> Result<()>
     where
         M: Message + Send + 'static,
         S: Handler<M>,
         M::Result: Send,
     {
-        let env = Envelope::new(message, None);
+        let env = Envelope::notify(message);
+
+        self.sender.send(env)?;
+
+        Ok(())
+    }
+
+    /// Call service's handler without result, with priority level
+    ///
+    /// Only works when `Context::priority` set, higher level is handled first.
+    /// Level out of range is treated as highest level.
+    pub fn send_with_priority<M>(&self, message: M, level: u8) -> Result<()>
+    where
+        M: Message + Send + 'static,
+        S: Handler<M>,
+        M::Result: Send,
+    {
+        let env = Envelope::notify_with_priority(message, level);
 
-        self.sender.send(env).map_err(|_| Error::ServiceStoped)?;
+        self.sender.send(env)?;
 
         Ok(())
     }
+
+    /// Call service's handler without result, get message back when failed
+    ///
+    /// Same as `send`, but if service stoped, the message will return in
+    /// `SendError`, so it can be retried or sent to other service.
+    pub fn try_send<M>(&self, message: M) -> std::result::Result<(), SendError<M>>
+    where
+        M: Message + Send + 'static,
+        S: Handler<M>,
+        M::Result: Send,
+    {
+        let env = Envelope::notify(message);
+
+        self.sender.send(env).map_err(|e| {
+            let message = e.0.into_message().expect("Envelope must contain message");
+
+            SendError(message)
+        })
+    }
 }
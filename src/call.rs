@@ -0,0 +1,101 @@
+use std::{
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
+
+use crate::{CallStack, Envelope, Error, Message, Result, ServiceId};
+
+/// Future returned by `Address::call`
+///
+/// Message is enqueued when this future polled first time, so dropping it
+/// before that will not send the message. After message enqueued, dropping
+/// it only drop the result, the handler will still be called.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Call<'a, S, M>
+where
+    M: Message,
+{
+    state: CallState<'a, S, M::Result>,
+}
+
+enum CallState<'a, S, R> {
+    Init {
+        target: ServiceId,
+        sender: &'a UnboundedSender<Envelope<S>>,
+        envelope: Envelope<S>,
+        receiver: oneshot::Receiver<R>,
+    },
+    Wait(oneshot::Receiver<R>),
+    Done,
+}
+
+impl<'a, S, M> Call<'a, S, M>
+where
+    M: Message,
+{
+    pub(crate) fn new(
+        target: ServiceId,
+        sender: &'a UnboundedSender<Envelope<S>>,
+        envelope: Envelope<S>,
+        receiver: oneshot::Receiver<M::Result>,
+    ) -> Self {
+        Self {
+            state: CallState::Init {
+                target,
+                sender,
+                envelope,
+                receiver,
+            },
+        }
+    }
+
+    /// Return true when this future already returned `Ready`.
+    ///
+    /// It's same as `FusedFuture::is_terminated`.
+    pub fn is_terminated(&self) -> bool {
+        matches!(self.state, CallState::Done)
+    }
+}
+
+impl<S, M> Future for Call<'_, S, M>
+where
+    M: Message,
+{
+    type Output = Result<M::Result>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let mut receiver = match mem::replace(&mut this.state, CallState::Done) {
+            CallState::Init {
+                target,
+                sender,
+                envelope,
+                receiver,
+            } => {
+                if let Err(e) = CallStack::check(target) {
+                    return Poll::Ready(Err(e));
+                }
+
+                if sender.send(envelope).is_err() {
+                    return Poll::Ready(Err(Error::ServiceStoped));
+                }
+                receiver
+            }
+            CallState::Wait(receiver) => receiver,
+            CallState::Done => panic!("`Call` polled after completion"),
+        };
+
+        match Pin::new(&mut receiver).poll(cx) {
+            Poll::Ready(r) => Poll::Ready(r.map_err(|_| Error::Canceled)),
+            Poll::Pending => {
+                this.state = CallState::Wait(receiver);
+                Poll::Pending
+            }
+        }
+    }
+}
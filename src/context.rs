@@ -1,12 +1,43 @@
-use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use crate::{Address, EnvelopProxy, Envelope, Service};
+use tokio::{
+    sync::{
+        mpsc::{error::TryRecvError, unbounded_channel, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+    task::JoinHandle,
+};
+
+use crate::{
+    event, panic_message, Address, BoxError, CallStack, CatchUnwind, Decision, EnvelopProxy,
+    Envelope, Error, ForwardError, Handler, Message, MessageMeta, Middleware, Result, Service,
+    ServiceEventKind, ServiceId, ServiceState, StartWith, StopHandle, Timer,
+};
 
 /// Context to run service
 pub struct Context<S> {
     sender: UnboundedSender<Envelope<S>>,
-    receiver: UnboundedReceiver<Envelope<S>>,
+    pub(crate) receiver: UnboundedReceiver<Envelope<S>>,
+    state: Arc<ServiceState>,
+    next_service: Option<S>,
+    pub(crate) deadline: Option<Instant>,
+    stop_when_unreferenced: bool,
+    middlewares: Vec<Box<dyn Middleware<S>>>,
+    drain_timeout: Option<Duration>,
+    drain_deadline: Option<Instant>,
+    dropped_messages: usize,
+    catch_panic: bool,
+    batch_size: Option<usize>,
+    priorities: Vec<VecDeque<Envelope<S>>>,
+    failure: Option<Error>,
+    on_drop: Vec<Box<dyn FnOnce() + Send>>,
     pub(crate) paused: bool,
+    detached: bool,
 }
 
 impl<S> Default for Context<S> {
@@ -15,6 +46,17 @@ impl<S> Default for Context<S> {
     }
 }
 
+impl<S> Drop for Context<S> {
+    fn drop(&mut self) {
+        // Wake `wait_ready` even if run loop panicked or aborted.
+        self.state.set_exited();
+
+        for f in self.on_drop.drain(..) {
+            f();
+        }
+    }
+}
+
 impl<S> Context<S> {
     /// Create an empty context
     pub fn new() -> Self {
@@ -23,30 +65,239 @@ impl<S> Context<S> {
         Self {
             sender,
             receiver,
+            state: Arc::new(ServiceState::default()),
+            next_service: None,
+            deadline: None,
+            stop_when_unreferenced: false,
+            middlewares: Vec::new(),
+            drain_timeout: None,
+            drain_deadline: None,
+            dropped_messages: 0,
+            catch_panic: false,
+            batch_size: None,
+            priorities: Vec::new(),
+            failure: None,
+            on_drop: Vec::new(),
             paused: false,
+            detached: false,
         }
     }
 
+    /// Get id of service
+    pub fn id(&self) -> ServiceId {
+        self.state.id
+    }
+
     /// Get service's address
     ///
     /// Even if service not start, you can also get an address.
     /// But if you send message, the message maybe lost.
     pub fn addr(&self) -> Address<S> {
-        Address {
-            sender: self.sender.clone(),
+        Address::new(self.sender.clone(), self.state.clone())
+    }
+
+    /// Count of addresses alive
+    ///
+    /// The sender held by context itself is not counted.
+    pub fn address_count(&self) -> usize {
+        self.state.address_count()
+    }
+
+    /// Stop service when all addresses dropped
+    ///
+    /// By default service keep running until stopped explicitly. If enabled,
+    /// service will stop after `last_address_dropped` hook, messages already
+    /// in mailbox will still be handled.
+    pub fn set_stop_when_unreferenced(&mut self, stop: bool) {
+        self.stop_when_unreferenced = stop;
+    }
+
+    /// Catch panic of handler
+    ///
+    /// By default, a panicking handler stop the whole service without call
+    /// `stopped`. If enabled, panic will be logged and that message dropped,
+    /// caller will get `Canceled`, then service continue to handle messages.
+    pub fn set_catch_panic(&mut self, catch_panic: bool) {
+        self.catch_panic = catch_panic;
+    }
+
+    /// Handle messages in batch
+    ///
+    /// If set, service take at most `max` pending messages once and pass them
+    /// to `Service::handle_batch`.
+    pub fn set_batch_size(&mut self, max: usize) {
+        self.batch_size = Some(max.max(1));
+    }
+
+    /// Handle messages by priority level
+    ///
+    /// Pending messages with higher level are handled first, messages with
+    /// same level keep order. Use `Address::send_with_priority` to set level.
+    pub fn priority(&mut self, levels: u8) {
+        self.priorities = (0..levels).map(|_| VecDeque::new()).collect();
+    }
+
+    /// Add a middleware run around every message
+    pub fn add_middleware(&mut self, middleware: impl Middleware<S>) {
+        self.middlewares.push(Box::new(middleware));
+    }
+
+    /// Get timer of service
+    pub fn timer(&self) -> Arc<dyn Timer> {
+        self.state.timer()
+    }
+
+    /// Set timer of service, default is `TokioTimer`
+    ///
+    /// Addresses of this service use the same timer.
+    pub fn set_timer(&mut self, timer: impl Timer) {
+        self.state.set_timer(Arc::new(timer));
+    }
+
+    /// Deadline of current message
+    ///
+    /// Only have value in handler when message sent by `Address::call_timeout`.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Time remaining before deadline of current message
+    ///
+    /// Return `Some(Duration::ZERO)` if deadline already passed.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        let deadline = self.deadline?;
+
+        Some(deadline.saturating_duration_since(self.timer().now()))
+    }
+
+    /// Get a handle to stop service from outside
+    pub fn stop_handle(&self) -> StopHandle {
+        StopHandle {
+            state: self.state.clone(),
         }
     }
 
+    /// Replace service with a new value
+    ///
+    /// The replacement happens after current message or hook finished, then
+    /// following messages will be handled by new value. Because handlers are
+    /// static dispatch, new value must be the same type, so use this with
+    /// an enum or a state field to change behavior.
+    ///
+    /// Old value will be dropped without call `stopped`, and `started`
+    /// will not be called on new value.
+    pub fn replace_service(&mut self, service: S) {
+        self.next_service = Some(service);
+    }
+
     /// Pause context
     ///
+    /// Results of calls are dropped after paused, callers get `Canceled`.
+    ///
     /// Notice: This funcion is unusable now.
     pub fn pause(&mut self) {
         self.paused = true;
     }
 
+    /// Return true when stop requested by `StopHandle`
+    ///
+    /// Also true after `stop` called on context from `into_parts`.
+    pub fn is_stop_requested(&self) -> bool {
+        self.state.is_stop_requested()
+    }
+
+    /// Split context into context and mailbox, to build a custom run loop
+    ///
+    /// Pass the returned context to `Envelope::handle`. When use parts, you must
+    /// call `started` and `stopped` hooks yourself. Don't `run` the returned
+    /// context.
+    ///
+    /// `stop` on returned context can't close the mailbox, it request stop
+    /// instead. Check `is_stop_requested` after each message in your loop, and
+    /// close the receiver yourself.
+    pub fn into_parts(mut self) -> (Self, UnboundedReceiver<Envelope<S>>) {
+        let (_, detached) = unbounded_channel();
+
+        let receiver = std::mem::replace(&mut self.receiver, detached);
+        self.detached = true;
+
+        (self, receiver)
+    }
+
+    /// Register a callback run when context dropped
+    ///
+    /// Unlike `stopped`, it still runs if the service future is cancelled or
+    /// aborted. When service stop normally, it runs after `stopped`.
+    /// Callbacks run in order of registration.
+    pub fn on_drop(&mut self, f: impl FnOnce() + Send + 'static) {
+        self.on_drop.push(Box::new(f));
+    }
+
+    /// Count of messages dropped without handle when service stopped
+    ///
+    /// This value is ready in `stopped` hook.
+    pub fn dropped_messages(&self) -> usize {
+        self.dropped_messages
+    }
+
     /// Stop an service
+    ///
+    /// New messages are rejected with `ServiceStoped`. Messages already in
+    /// mailbox are still handled, unless dropped by drain timeout or
+    /// `StopHandle`, then their callers get `Canceled`. No call will hang.
     pub fn stop(&mut self) {
-        self.receiver.close()
+        if self.detached {
+            self.state.request_stop();
+        }
+
+        let now = self.timer().now();
+
+        self.stop_at(now);
+    }
+
+    /// Stop an service with an error
+    ///
+    /// The error is returned by the handle of `run_fallible`, only the first
+    /// error is kept.
+    pub fn fail(&mut self, error: impl Into<BoxError>) {
+        if self.failure.is_none() {
+            self.failure = Some(Error::Failed(error.into()));
+        }
+
+        self.stop();
+    }
+
+    fn try_recv(&mut self) -> std::result::Result<Envelope<S>, TryRecvError> {
+        if self.priorities.is_empty() {
+            return self.receiver.try_recv();
+        }
+
+        while let Ok(e) = self.receiver.try_recv() {
+            self.push_priority(e);
+        }
+
+        for queue in self.priorities.iter_mut().rev() {
+            if let Some(e) = queue.pop_front() {
+                return Ok(e);
+            }
+        }
+
+        self.receiver.try_recv()
+    }
+
+    fn push_priority(&mut self, e: Envelope<S>) {
+        let level = usize::from(e.priority()).min(self.priorities.len() - 1);
+
+        self.priorities[level].push_back(e);
+    }
+
+    fn stop_at(&mut self, at: Instant) {
+        self.receiver.close();
+
+        if let (Some(timeout), None) = (self.drain_timeout, self.drain_deadline) {
+            // Too large timeout means no deadline.
+            self.drain_deadline = at.checked_add(timeout);
+        }
     }
 }
 
@@ -54,22 +305,391 @@ impl<S> Context<S>
 where
     S: Service + Send,
 {
+    /// Send a message to service itself
+    ///
+    /// Message will be handled after current message.
+    pub fn notify<M>(&self, message: M) -> Result<()>
+    where
+        M: Message + Send + 'static,
+        S: Handler<M>,
+        M::Result: Send,
+    {
+        let env = Envelope::notify(message);
+
+        self.sender.send(env)?;
+
+        Ok(())
+    }
+
+    /// Call service itself and get result
+    ///
+    /// Message is enqueued immediately, the returned future don't borrow context.
+    ///
+    /// Notice: Don't await it in handler or hooks of this service, it will never
+    /// finish because current message block the mailbox. Spawn it instead.
+    /// With `deadlock-detection` feature, the future return `WouldDeadlock` if
+    /// polled in handler of this service, the message is still handled later.
+    /// Checking is done when polled instead of before send, so creating it in
+    /// handler and spawning it still works.
+    pub fn notify_call<M>(&self, message: M) -> impl Future<Output = Result<M::Result>> + 'static
+    where
+        M: Message + Send + 'static,
+        S: Handler<M>,
+        M::Result: Send,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        let env = Envelope::new(message, sender);
+
+        let sent = self.sender.send(env).map_err(|_| Error::ServiceStoped);
+        let id = self.id();
+
+        async move {
+            sent?;
+            CallStack::check(id)?;
+
+            Ok(receiver.await?)
+        }
+    }
+
+    /// Forward an envelope to other service without re-allocation
+    ///
+    /// Result of call will be sent by `to`. If message in envelope isn't `M`,
+    /// envelope will be returned in `MessageMismatch`.
+    pub fn forward<M, S2>(
+        &self,
+        envelope: Envelope<S>,
+        to: &Address<S2>,
+    ) -> std::result::Result<(), ForwardError<S>>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+        S2: Service + Handler<M>,
+    {
+        envelope.forward::<M, S2>(to)
+    }
+
     /// Start an service
     pub fn run(self, service: S) -> Address<S> {
-        let mut this = self;
+        let address = self.addr();
+
+        tokio::spawn(self.serve(service, None));
+
+        address
+    }
+
+    /// Start an service, and get a receiver for the result of `init` hook
+    ///
+    /// If `init` failed, the receiver will get `InitFailed`.
+    pub fn run_checked(self, service: S) -> (Address<S>, oneshot::Receiver<Result<()>>) {
+        let address = self.addr();
+
+        let (sender, receiver) = oneshot::channel();
+
+        tokio::spawn(self.serve(service, Some(sender)));
+
+        (address, receiver)
+    }
+
+    /// Start an service, and get a handle to wait it stopped
+    ///
+    /// The handle output `InitFailed` if `init` failed, or the error passed
+    /// to `Context::fail`.
+    pub fn run_fallible(self, service: S) -> (Address<S>, JoinHandle<Result<()>>) {
+        let address = self.addr();
+
+        let handle = tokio::spawn(self.serve(service, None));
+
+        (address, handle)
+    }
+
+    /// Start an service, limit time to drain mailbox when stopping
+    ///
+    /// After `stop` called or `StopHandle` used, service keep handling messages
+    /// in mailbox, but stop after `drain_timeout` even if messages remain.
+    /// Count of dropped messages can be read by `dropped_messages` in `stopped`.
+    pub fn run_with_shutdown(mut self, service: S, drain_timeout: Duration) -> Address<S> {
+        self.drain_timeout = Some(drain_timeout);
 
-        let address = this.addr();
+        self.run(service)
+    }
 
-        let mut service = service;
+    /// Start an service with argument
+    ///
+    /// `started_with` hook is called after `init` and before `started`.
+    pub fn run_with<A>(self, service: S, args: A) -> Address<S>
+    where
+        S: StartWith<A>,
+        A: Send + 'static,
+    {
+        let address = self.addr();
 
         tokio::spawn(async move {
-            service.started(&mut this).await;
-            while let Some(mut e) = this.receiver.recv().await {
-                e.handle(&mut service, &mut this).await;
+            let mut this = self;
+            let mut service = service;
+
+            if this.init_service(&mut service, None).await {
+                service.started_with(args, &mut this).await;
+                let _ = this.serve_started(service).await;
             }
-            service.stopped(&mut this).await;
         });
 
         address
     }
+
+    async fn serve(
+        mut self,
+        mut service: S,
+        init_result: Option<oneshot::Sender<Result<()>>>,
+    ) -> Result<()> {
+        if self.init_service(&mut service, init_result).await {
+            self.serve_started(service).await
+        } else {
+            self.failure.take().map_or(Ok(()), Err)
+        }
+    }
+
+    /// Call `init` hook, return false if failed and service already stopped.
+    async fn init_service(
+        &mut self,
+        service: &mut S,
+        init_result: Option<oneshot::Sender<Result<()>>>,
+    ) -> bool {
+        if let Err(e) = service.init(self).await {
+            log::warn!("Service {} init failed: {}", self.id(), e);
+
+            self.stop();
+            service.stopped(self).await;
+
+            self.state.set_exited();
+            event::publish::<S>(self.id(), ServiceEventKind::Stopped);
+
+            match init_result {
+                Some(sender) => {
+                    let _ = sender.send(Err(Error::InitFailed(e)));
+                }
+                None => self.failure = Some(Error::InitFailed(e)),
+            }
+            return false;
+        }
+
+        if let Some(sender) = init_result {
+            let _ = sender.send(Ok(()));
+        }
+
+        true
+    }
+
+    async fn serve_started(mut self, mut service: S) -> Result<()> {
+        service.started(&mut self).await;
+        self.state.set_ready();
+        event::publish::<S>(self.id(), ServiceEventKind::Started);
+
+        // Only call `idle` when mailbox become empty after handle messages.
+        let mut idle = true;
+
+        loop {
+            if let Some(next) = self.next_service.take() {
+                service = next;
+            }
+
+            if self.state.is_stop_requested() && self.drain_deadline.is_none() {
+                if self.drain_timeout.is_none() {
+                    break;
+                }
+
+                let at = self.state.stop_requested_at();
+                self.stop_at(at.unwrap_or_else(|| self.timer().now()));
+            }
+
+            if let Some(deadline) = self.drain_deadline {
+                if self.timer().now() >= deadline {
+                    break;
+                }
+            }
+
+            let e = match self.try_recv() {
+                Ok(e) => e,
+                Err(TryRecvError::Empty) if !idle => {
+                    idle = true;
+                    service.idle(&mut self).await;
+                    continue;
+                }
+                Err(TryRecvError::Empty) => {
+                    let state = self.state.clone();
+                    let timer = self.timer();
+                    let deadline = self.drain_deadline;
+
+                    tokio::select! {
+                        e = self.receiver.recv() => match e {
+                            // Queue it, so higher level sent meanwhile is handled first.
+                            Some(e) if !self.priorities.is_empty() => {
+                                self.push_priority(e);
+                                continue;
+                            }
+                            Some(e) => e,
+                            None => break,
+                        },
+                        _ = state.last_address_dropped() => {
+                            if state.address_count() == 0 {
+                                service.last_address_dropped(&mut self).await;
+
+                                if self.stop_when_unreferenced {
+                                    self.stop();
+                                }
+                            }
+                            continue;
+                        }
+                        _ = state.stop_requested() => continue,
+                        _ = wait_deadline(timer, deadline) => continue,
+                    }
+                }
+                Err(TryRecvError::Disconnected) => break,
+            };
+
+            idle = false;
+
+            if let Some(max) = self.batch_size {
+                let mut batch = vec![e];
+                while batch.len() < max {
+                    match self.try_recv() {
+                        Ok(e) => batch.push(e),
+                        Err(_) => break,
+                    }
+                }
+
+                service.handle_batch(batch, &mut self).await;
+            } else {
+                self.dispatch(&mut service, e).await;
+            }
+        }
+
+        if let Some(next) = self.next_service.take() {
+            service = next;
+        }
+
+        self.receiver.close();
+        let mut pending: Vec<_> = self
+            .priorities
+            .iter_mut()
+            .flat_map(|q| q.drain(..))
+            .collect();
+        while let Ok(e) = self.receiver.try_recv() {
+            log::debug!(
+                "Service {} not handled {}",
+                self.id(),
+                e.message_type_name()
+            );
+            pending.push(e);
+        }
+
+        self.dropped_messages += pending.len();
+        if !pending.is_empty() {
+            service.on_stop_drain(pending, &mut self).await;
+        }
+
+        if self.dropped_messages != 0 {
+            log::warn!(
+                "Service {} dropped {} messages when stopped",
+                self.id(),
+                self.dropped_messages
+            );
+        }
+
+        service.stopped(&mut self).await;
+        self.state.set_exited();
+        event::publish::<S>(self.id(), ServiceEventKind::Stopped);
+
+        self.failure.take().map_or(Ok(()), Err)
+    }
+
+    /// Handle an envelope same as run loop
+    ///
+    /// Middlewares, panic catching and deadlock detection are applied, but
+    /// `Envelope::handle` only call handler.
+    pub async fn dispatch(&mut self, service: &mut S, mut e: Envelope<S>) {
+        let meta = MessageMeta {
+            service: self.id(),
+            message_type: e.message_type_name(),
+            deadline: e.deadline(),
+        };
+
+        // Take middlewares out to pass context to them.
+        let mut middlewares = std::mem::take(&mut self.middlewares);
+
+        let mut passed = 0;
+        for middleware in middlewares.iter_mut() {
+            if middleware.before(&meta, self).await == Decision::Skip {
+                break;
+            }
+            passed += 1;
+        }
+
+        if passed == middlewares.len() {
+            let begin = log::log_enabled!(log::Level::Trace).then(|| self.timer().now());
+            let callers = e.callers();
+
+            let panicked = if self.catch_panic {
+                let handle = callers.scope(meta.service, e.handle(service, self));
+
+                match CatchUnwind::new(handle).await {
+                    Ok(()) => false,
+                    Err(e) => {
+                        log::error!(
+                            "Service {} panicked when handle {}: {}",
+                            meta.service,
+                            meta.message_type,
+                            panic_message(&*e)
+                        );
+
+                        // Handler didn't finish, reset state set by it.
+                        self.deadline = None;
+                        event::publish::<S>(meta.service, ServiceEventKind::HandlerPanicked);
+
+                        true
+                    }
+                }
+            } else {
+                callers.scope(meta.service, e.handle(service, self)).await;
+
+                false
+            };
+
+            if !panicked {
+                if let Some(begin) = begin {
+                    log::trace!(
+                        "Service {} handled {} in {:?}",
+                        meta.service,
+                        meta.message_type,
+                        self.timer().now() - begin
+                    );
+                }
+
+                for middleware in middlewares.iter_mut().rev() {
+                    middleware.after(&meta, self).await;
+                }
+            }
+        } else {
+            log::debug!(
+                "Service {} skipped {} by middleware",
+                meta.service,
+                meta.message_type
+            );
+        }
+
+        // Keep middlewares added while handling.
+        middlewares.append(&mut self.middlewares);
+        self.middlewares = middlewares;
+    }
+}
+
+async fn wait_deadline(timer: Arc<dyn Timer>, deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => {
+            timer
+                .sleep(deadline.saturating_duration_since(timer.now()))
+                .await
+        }
+        None => std::future::pending().await,
+    }
 }
@@ -0,0 +1,88 @@
+use std::future::Future;
+
+use crate::{Result, ServiceId};
+
+#[cfg(feature = "deadlock-detection")]
+use std::sync::Arc;
+
+#[cfg(feature = "deadlock-detection")]
+use crate::Error;
+
+#[cfg(feature = "deadlock-detection")]
+tokio::task_local! {
+    static CALL_STACK: Arc<[ServiceId]>;
+}
+
+/// Services waiting for result of a call
+///
+/// Carried by envelope, so handler known who is waiting for it.
+#[cfg(feature = "deadlock-detection")]
+#[derive(Clone)]
+pub(crate) struct CallStack(Option<Arc<[ServiceId]>>);
+
+#[cfg(not(feature = "deadlock-detection"))]
+#[derive(Clone)]
+pub(crate) struct CallStack;
+
+#[cfg(feature = "deadlock-detection")]
+impl CallStack {
+    /// Call stack of message not sent in handler
+    pub fn empty() -> Self {
+        Self(None)
+    }
+
+    /// Get call stack of current handler
+    pub fn current() -> Self {
+        Self(CALL_STACK.try_with(Arc::clone).ok())
+    }
+
+    /// Return `WouldDeadlock` if `target` is waiting for current handler
+    pub fn check(target: ServiceId) -> Result<()> {
+        let found = CALL_STACK
+            .try_with(|stack| stack.contains(&target))
+            .unwrap_or(false);
+
+        if found {
+            Err(Error::WouldDeadlock)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Run handler of service `id` with this call stack
+    pub async fn scope<F>(self, id: ServiceId, f: F) -> F::Output
+    where
+        F: Future,
+    {
+        let stack = self
+            .0
+            .iter()
+            .flat_map(|s| s.iter().copied())
+            .chain(Some(id))
+            .collect();
+
+        CALL_STACK.scope(stack, f).await
+    }
+}
+
+#[cfg(not(feature = "deadlock-detection"))]
+impl CallStack {
+    pub fn empty() -> Self {
+        Self
+    }
+
+    pub fn current() -> Self {
+        Self
+    }
+
+    pub fn check(_target: ServiceId) -> Result<()> {
+        Ok(())
+    }
+
+    pub async fn scope<F>(self, _id: ServiceId, f: F) -> F::Output
+    where
+        F: Future,
+    {
+        f.await
+    }
+}
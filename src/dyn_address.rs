@@ -0,0 +1,147 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::{self, Debug},
+    sync::Arc,
+};
+
+use tokio::sync::oneshot;
+
+use crate::{Address, CallStack, Envelope, Error, Handler, Message, Result, Service, ServiceId};
+
+type DynHandler<M> =
+    Box<dyn Fn(M, Option<oneshot::Sender<<M as Message>::Result>>) -> Result<()> + Send + Sync>;
+
+/// Address erased the type of service
+///
+/// Only messages registered by `DynAddressBuilder::handle` can be sent, other
+/// messages will get `MessageNotHandled`.
+#[derive(Clone)]
+pub struct DynAddress {
+    id: ServiceId,
+    handlers: Arc<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+    is_stop: Arc<dyn Fn() -> bool + Send + Sync>,
+}
+
+impl Debug for DynAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynAddress")
+            .field("id", &self.id)
+            .field("messages", &self.handlers.len())
+            .field("stopped", &self.is_stop())
+            .finish()
+    }
+}
+
+impl DynAddress {
+    /// Get id of service
+    pub fn id(&self) -> ServiceId {
+        self.id
+    }
+
+    /// Return true when service stopped.
+    pub fn is_stop(&self) -> bool {
+        (self.is_stop)()
+    }
+
+    /// Return true if message can be handled by service
+    pub fn can_handle<M>(&self) -> bool
+    where
+        M: 'static,
+    {
+        self.handlers.contains_key(&TypeId::of::<M>())
+    }
+
+    fn handler<M>(&self) -> Result<&DynHandler<M>>
+    where
+        M: Message + 'static,
+        M::Result: 'static,
+    {
+        self.handlers
+            .get(&TypeId::of::<M>())
+            .and_then(|h| h.downcast_ref())
+            .ok_or(Error::MessageNotHandled)
+    }
+
+    /// Call service's handler and get result
+    pub async fn call<M>(&self, message: M) -> Result<M::Result>
+    where
+        M: Message + 'static,
+        M::Result: 'static,
+    {
+        CallStack::check(self.id)?;
+
+        let (sender, receiver) = oneshot::channel();
+
+        self.handler::<M>()?(message, Some(sender))?;
+
+        Ok(receiver.await?)
+    }
+
+    /// Call service's handler without result
+    pub fn send<M>(&self, message: M) -> Result<()>
+    where
+        M: Message + 'static,
+        M::Result: 'static,
+    {
+        self.handler::<M>()?(message, None)
+    }
+}
+
+/// Builder to register messages of `DynAddress`
+pub struct DynAddressBuilder<S> {
+    address: Address<S>,
+    handlers: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl<S> DynAddressBuilder<S>
+where
+    S: Service,
+{
+    /// Register a message handled by service
+    pub fn handle<M>(mut self) -> Self
+    where
+        M: Message + Send + 'static,
+        S: Handler<M>,
+        M::Result: Send + 'static,
+    {
+        let address = self.address.clone();
+
+        let handler: DynHandler<M> = Box::new(move |message, result_channel| {
+            let env = match result_channel {
+                Some(rc) => Envelope::new(message, rc),
+                None => Envelope::notify(message),
+            };
+
+            address.sender.send(env).map_err(|_| Error::ServiceStoped)
+        });
+
+        self.handlers.insert(TypeId::of::<M>(), Box::new(handler));
+
+        self
+    }
+
+    /// Build `DynAddress`
+    pub fn build(self) -> DynAddress {
+        let address = self.address;
+
+        DynAddress {
+            id: address.id(),
+            handlers: Arc::new(self.handlers),
+            is_stop: Arc::new(move || address.is_stop()),
+        }
+    }
+}
+
+impl<S> Address<S>
+where
+    S: Service,
+{
+    /// Erase service type of this address.
+    pub fn into_dyn(self) -> DynAddressBuilder<S> {
+        DynAddressBuilder {
+            address: self,
+            handlers: HashMap::new(),
+        }
+    }
+}
@@ -1,15 +1,38 @@
+use std::{
+    any::{Any, TypeId},
+    time::Instant,
+};
+
 use async_trait::async_trait;
 use tokio::sync::oneshot;
 
-use crate::{Context, Handler, Message, Service};
+use crate::{Address, CallStack, Context, ForwardError, Handler, Message, Service};
 
-pub(crate) struct Envelope<S>(Box<dyn EnvelopProxy<S> + Send>);
+/// Type erased message sent to service
+///
+/// Carry the message and an optional result channel.
+pub struct Envelope<S>(Box<dyn EnvelopProxy<S> + Send>);
 
 impl<S> Envelope<S>
 where
     S: Service + Send,
 {
-    pub fn new<M>(message: M, result_channel: Option<oneshot::Sender<M::Result>>) -> Self
+    /// Create an envelope, the result will be sent to result channel.
+    pub fn new<M>(message: M, result_channel: oneshot::Sender<M::Result>) -> Self
+    where
+        S: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        Self::with_deadline(message, result_channel, None)
+    }
+
+    /// Create an envelope, the deadline can be read by `Context::deadline` in handler.
+    pub fn with_deadline<M>(
+        message: M,
+        result_channel: oneshot::Sender<M::Result>,
+        deadline: Option<Instant>,
+    ) -> Self
     where
         S: Handler<M>,
         M: Message + Send + 'static,
@@ -17,11 +40,128 @@ where
     {
         Self(Box::new(EnvelopWithMessage {
             message: Some(message),
-            result_channel,
+            result_channel: Some(result_channel),
+            deadline,
+            callers: CallStack::current(),
+        }))
+    }
+
+    /// Create an envelope without result channel, the result will be dropped.
+    pub fn notify<M>(message: M) -> Self
+    where
+        S: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        Self::notify_with_priority(message, 0)
+    }
+
+    /// Create an envelope without result channel, with priority level.
+    pub fn notify_with_priority<M>(message: M, priority: u8) -> Self
+    where
+        S: Handler<M>,
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        Self(Box::new(EnvelopWithNotification {
+            message: Some(message),
+            priority,
         }))
     }
 }
 
+impl<S> Envelope<S>
+where
+    S: Send,
+{
+    /// Call handler of service with this envelope
+    ///
+    /// Result will be sent to caller if needed. Middlewares are not applied,
+    /// use `Context::dispatch` to handle it same as run loop.
+    pub async fn handle(&mut self, svc: &mut S, ctx: &mut Context<S>) {
+        self.0.handle(svc, ctx).await
+    }
+}
+
+impl<S> Envelope<S> {
+    /// Type name of message, only for debugging
+    pub fn message_type_name(&self) -> &'static str {
+        self.0.message_type_name()
+    }
+
+    /// Type id of message
+    pub fn message_type_id(&self) -> TypeId {
+        self.0.message_type_id()
+    }
+
+    /// Priority level of envelope, 0 by default
+    pub fn priority(&self) -> u8 {
+        self.0.priority()
+    }
+
+    /// Take message out, return `None` if message isn't `M` or already taken.
+    pub fn into_message<M>(mut self) -> Option<M>
+    where
+        M: 'static,
+    {
+        let message = self.0.take_message()?;
+
+        message.downcast().ok().map(|m| *m)
+    }
+
+    /// Send result to caller without call handler
+    ///
+    /// Return envelope back if message isn't `M`. Result of notification is dropped.
+    pub fn reply<M>(self, result: M::Result) -> Result<(), Self>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+    {
+        if self.0.message_type_id() != TypeId::of::<M>() {
+            return Err(self);
+        }
+
+        if let Ok(mut e) = self.0.into_any().downcast::<EnvelopWithMessage<M>>() {
+            if let Some(rc) = e.result_channel.take() {
+                if rc.send(result).is_err() {
+                    log::warn!("Channel Closed");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Move envelope to other service without re-allocation
+    ///
+    /// The result channel moved together, so caller will get result from `to`.
+    pub(crate) fn forward<M, S2>(self, to: &Address<S2>) -> Result<(), ForwardError<S>>
+    where
+        M: Message + Send + 'static,
+        M::Result: Send,
+        S2: Service + Handler<M>,
+    {
+        if self.0.message_type_id() != TypeId::of::<M>() {
+            return Err(ForwardError::MessageMismatch(self));
+        }
+
+        let envelope = self.0.into_any();
+
+        let proxy: Box<dyn EnvelopProxy<S2> + Send> =
+            match envelope.downcast::<EnvelopWithMessage<M>>() {
+                Ok(e) => e,
+                Err(envelope) => match envelope.downcast::<EnvelopWithNotification<M>>() {
+                    Ok(e) => e,
+                    Err(_) => unreachable!("Message type already checked"),
+                },
+            };
+
+        to.sender
+            .send(Envelope(proxy))
+            .map_err(|_| ForwardError::ServiceStoped)
+    }
+}
+
 #[async_trait]
 impl<S> EnvelopProxy<S> for Envelope<S>
 where
@@ -32,25 +172,75 @@ where
 
         r.handle(svc, ctx).await
     }
+
+    fn take_message(&mut self) -> Option<Box<dyn Any + Send>> {
+        self.0.take_message()
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        self.0.message_type_name()
+    }
+
+    fn message_type_id(&self) -> TypeId {
+        self.0.message_type_id()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self.0.into_any()
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.0.deadline()
+    }
+
+    fn priority(&self) -> u8 {
+        self.0.priority()
+    }
+
+    fn callers(&self) -> CallStack {
+        self.0.callers()
+    }
 }
 
 #[async_trait]
 pub(crate) trait EnvelopProxy<S> {
     async fn handle(&mut self, svc: &mut S, ctx: &mut Context<S>);
+
+    fn take_message(&mut self) -> Option<Box<dyn Any + Send>>;
+
+    fn message_type_name(&self) -> &'static str;
+
+    fn message_type_id(&self) -> TypeId;
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send>;
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn priority(&self) -> u8 {
+        0
+    }
+
+    fn callers(&self) -> CallStack {
+        CallStack::empty()
+    }
 }
 
-pub(crate) struct EnvelopWithMessage<M>
+struct EnvelopWithMessage<M>
 where
     M: Message,
 {
     message: Option<M>,
     result_channel: Option<oneshot::Sender<M::Result>>,
+    deadline: Option<Instant>,
+    callers: CallStack,
 }
 
 #[async_trait]
 impl<S, M> EnvelopProxy<S> for EnvelopWithMessage<M>
 where
-    M: Message + Send,
+    M: Message + Send + 'static,
     S: Service + Handler<M> + Send,
     M::Result: Send,
 {
@@ -58,15 +248,86 @@ where
         let message = self.message.take();
         let result_channel = self.result_channel.take();
 
-        if let (Some(message), Some(mut rc)) = (message, result_channel) {
+        if let (Some(message), Some(rc)) = (message, result_channel) {
+            ctx.deadline = self.deadline;
             let res = <S as Handler<M>>::handler(svc, message, ctx).await;
+            ctx.deadline = None;
 
             if ctx.paused {
-                log::info!("Call a closed service");
-                rc.closed().await;
+                // Drop result channel, so caller won't wait forever.
+                log::info!("Call a paused service {}", ctx.id());
             } else if rc.send(res).is_err() {
-                log::warn!("Channel Closed");
+                log::warn!("Channel Closed, service {}", ctx.id());
+
+                svc.call_abandoned(std::any::type_name::<M>(), ctx).await;
             }
         }
     }
+
+    fn take_message(&mut self) -> Option<Box<dyn Any + Send>> {
+        let message = self.message.take()?;
+
+        Some(Box::new(message))
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn message_type_id(&self) -> TypeId {
+        TypeId::of::<M>()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    fn callers(&self) -> CallStack {
+        self.callers.clone()
+    }
+}
+
+struct EnvelopWithNotification<M> {
+    message: Option<M>,
+    priority: u8,
+}
+
+#[async_trait]
+impl<S, M> EnvelopProxy<S> for EnvelopWithNotification<M>
+where
+    M: Message + Send + 'static,
+    S: Service + Handler<M> + Send,
+    M::Result: Send,
+{
+    async fn handle(&mut self, svc: &mut S, ctx: &mut Context<S>) {
+        if let Some(message) = self.message.take() {
+            <S as Handler<M>>::handler(svc, message, ctx).await;
+        }
+    }
+
+    fn take_message(&mut self) -> Option<Box<dyn Any + Send>> {
+        let message = self.message.take()?;
+
+        Some(Box::new(message))
+    }
+
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn message_type_id(&self) -> TypeId {
+        TypeId::of::<M>()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self
+    }
+
+    fn priority(&self) -> u8 {
+        self.priority
+    }
 }
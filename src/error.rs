@@ -1,4 +1,9 @@
+use std::fmt::{self, Debug, Display};
+
 use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::Envelope;
 
 /// Error
 #[derive(Debug, Error)]
@@ -7,10 +12,166 @@ pub enum Error {
     #[error("Service already stoped")]
     ServiceStoped,
 
-    /// This query is send, can't read result
+    /// Service is paused
+    ///
+    /// Unused now, call to a paused service get `Canceled`.
+    #[deprecated(note = "call to a paused service get `Canceled`")]
     #[error("Service is paused")]
     ServicePaused,
+
+    /// Call not finished in time
+    #[error("Call timeout")]
+    Timeout,
+
+    /// Mailbox of service is full
+    #[error("Service mailbox is full")]
+    MailboxFull,
+
+    /// Message dropped before service reply, like service stoped after message enqueued
+    #[error("Call canceled")]
+    Canceled,
+
+    /// Message isn't registered on `DynAddress`
+    #[error("Message not handled by service")]
+    MessageNotHandled,
+
+    /// Service `init` hook return an error
+    #[error("Service init failed: {0}")]
+    InitFailed(BoxError),
+
+    /// Callee is waiting for caller, only with `deadlock-detection` feature
+    #[error("Call would deadlock")]
+    WouldDeadlock,
+
+    /// Service stopped by `Context::fail`
+    #[error("Service failed: {0}")]
+    Failed(BoxError),
 }
 
+impl<T> From<mpsc::error::SendError<T>> for Error {
+    fn from(_: mpsc::error::SendError<T>) -> Self {
+        Error::ServiceStoped
+    }
+}
+
+impl From<oneshot::error::RecvError> for Error {
+    fn from(_: oneshot::error::RecvError) -> Self {
+        Error::Canceled
+    }
+}
+
+/// Boxed error carried by `InitFailed` and `Failed`
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Error returned by `Service::init`
+pub type InitError = BoxError;
+
+/// Error of `Address::try_send`
+///
+/// Service already stoped, carry the message which can't be sent.
+pub struct SendError<M>(pub M);
+
+impl<M> SendError<M> {
+    /// Get the message back
+    pub fn into_inner(self) -> M {
+        self.0
+    }
+}
+
+impl<M> Debug for SendError<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<M> Display for SendError<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Service already stoped")
+    }
+}
+
+impl<M> std::error::Error for SendError<M> {}
+
+impl<M> From<SendError<M>> for Error {
+    fn from(_: SendError<M>) -> Self {
+        Error::ServiceStoped
+    }
+}
+
+/// Error of `Address::try_call`
+pub enum CallError<M> {
+    /// Message not delivered because service stoped, message returned
+    NotDelivered(M),
+
+    /// Message delivered but dropped before reply, it may be handled
+    NoReply,
+
+    /// Message not delivered because service is waiting for caller, message returned
+    ///
+    /// Only with `deadlock-detection` feature.
+    WouldDeadlock(M),
+}
+
+impl<M> Debug for CallError<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotDelivered(_) => f.write_str("NotDelivered(..)"),
+            Self::NoReply => f.write_str("NoReply"),
+            Self::WouldDeadlock(_) => f.write_str("WouldDeadlock(..)"),
+        }
+    }
+}
+
+impl<M> Display for CallError<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotDelivered(_) => write!(f, "Message not delivered"),
+            Self::NoReply => write!(f, "Message delivered but no reply"),
+            Self::WouldDeadlock(_) => write!(f, "Call would deadlock"),
+        }
+    }
+}
+
+impl<M> std::error::Error for CallError<M> {}
+
+impl<M> From<CallError<M>> for Error {
+    fn from(e: CallError<M>) -> Self {
+        match e {
+            CallError::NotDelivered(_) => Error::ServiceStoped,
+            CallError::NoReply => Error::Canceled,
+            CallError::WouldDeadlock(_) => Error::WouldDeadlock,
+        }
+    }
+}
+
+/// Error of `Context::forward`
+pub enum ForwardError<S> {
+    /// Message in envelope isn't the given type, envelope returned
+    MessageMismatch(Envelope<S>),
+
+    /// Target service already stoped, envelope dropped
+    ServiceStoped,
+}
+
+impl<S> Debug for ForwardError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MessageMismatch(_) => f.write_str("MessageMismatch(..)"),
+            Self::ServiceStoped => f.write_str("ServiceStoped"),
+        }
+    }
+}
+
+impl<S> Display for ForwardError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MessageMismatch(_) => write!(f, "Message type mismatch"),
+            Self::ServiceStoped => write!(f, "Service already stoped"),
+        }
+    }
+}
+
+impl<S> std::error::Error for ForwardError<S> {}
+
 /// Result
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,54 @@
+use std::sync::OnceLock;
+
+use tokio::sync::broadcast;
+
+use crate::ServiceId;
+
+const EVENTS_CAPACITY: usize = 1024;
+
+static EVENTS: OnceLock<broadcast::Sender<ServiceEvent>> = OnceLock::new();
+
+/// Lifecycle event of service
+#[derive(Debug, Clone)]
+pub struct ServiceEvent {
+    /// Id of service
+    pub id: ServiceId,
+    /// Type name of service
+    pub service: &'static str,
+    /// Kind of event
+    pub kind: ServiceEventKind,
+}
+
+/// Kind of service event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceEventKind {
+    /// `started` hook finished
+    Started,
+    /// `stopped` hook finished
+    Stopped,
+    /// Handler panicked, only when `Context::set_catch_panic` enabled
+    HandlerPanicked,
+}
+
+fn sender() -> &'static broadcast::Sender<ServiceEvent> {
+    EVENTS.get_or_init(|| broadcast::channel(EVENTS_CAPACITY).0)
+}
+
+/// Subscribe lifecycle events of all services
+///
+/// Events published before subscribe will not be received. If receiver is
+/// too slow, it will get `RecvError::Lagged`.
+pub fn events() -> broadcast::Receiver<ServiceEvent> {
+    sender().subscribe()
+}
+
+pub(crate) fn publish<S>(id: ServiceId, kind: ServiceEventKind) {
+    let event = ServiceEvent {
+        id,
+        service: std::any::type_name::<S>(),
+        kind,
+    };
+
+    // No subscriber, drop event.
+    let _ = sender().send(event);
+}
@@ -18,3 +18,15 @@ pub trait Message {
     /// Result of message
     type Result;
 }
+
+/// Message without result
+///
+/// Impl this trait to get `Message` with `()` result, like `impl Notification for Ping {}`.
+pub trait Notification {}
+
+impl<T> Message for T
+where
+    T: Notification,
+{
+    type Result = ();
+}
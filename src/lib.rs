@@ -8,10 +8,49 @@ mod handler;
 pub use handler::*;
 
 mod envelop;
-pub(crate) use envelop::*;
+pub(crate) use envelop::EnvelopProxy;
+pub use envelop::Envelope;
+
+mod id;
+pub use id::*;
+
+mod state;
+pub(crate) use state::*;
 
 mod error;
 pub use error::*;
 
 mod address;
 pub use address::*;
+
+mod middleware;
+pub use middleware::*;
+
+mod event;
+pub use event::*;
+
+mod timer;
+pub use timer::*;
+
+mod deadlock;
+pub(crate) use deadlock::*;
+
+mod catch_unwind;
+pub(crate) use catch_unwind::*;
+
+mod call;
+pub use call::*;
+
+mod stop_handle;
+pub use stop_handle::*;
+
+mod dyn_address;
+pub use dyn_address::*;
+
+mod local;
+pub use local::*;
+
+#[cfg(feature = "test-util")]
+mod test_util;
+#[cfg(feature = "test-util")]
+pub use test_util::*;
@@ -0,0 +1,201 @@
+use async_trait::async_trait;
+use tokio::sync::{
+    mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    oneshot,
+};
+
+use crate::{Message, Result};
+
+/// A service running on current thread
+///
+/// Same as `Service`, but service, handler and message don't need `Send`.
+/// Local service must start inside a `tokio::task::LocalSet`.
+#[async_trait(?Send)]
+pub trait LocalService: Sized + 'static {
+    fn start(self) -> LocalAddress<Self> {
+        LocalContext::new().run(self)
+    }
+
+    fn start_by_context(self, ctx: LocalContext<Self>) -> LocalAddress<Self> {
+        ctx.run(self)
+    }
+
+    /// Hook for service started
+    async fn started(&mut self, _ctx: &mut LocalContext<Self>) {}
+
+    /// Hook for service stopped
+    async fn stopped(&mut self, _ctx: &mut LocalContext<Self>) {}
+}
+
+/// Handler message on local service
+#[async_trait(?Send)]
+pub trait LocalHandler<M>
+where
+    Self: LocalService,
+    M: Message,
+{
+    /// Handle message
+    async fn handler(&mut self, message: M, ctx: &mut LocalContext<Self>) -> M::Result;
+}
+
+/// Context to run local service
+pub struct LocalContext<S> {
+    sender: UnboundedSender<LocalEnvelope<S>>,
+    receiver: UnboundedReceiver<LocalEnvelope<S>>,
+}
+
+impl<S> Default for LocalContext<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> LocalContext<S> {
+    /// Create an empty context
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded_channel();
+
+        Self { sender, receiver }
+    }
+
+    /// Get service's address
+    pub fn addr(&self) -> LocalAddress<S> {
+        LocalAddress {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Stop an service
+    pub fn stop(&mut self) {
+        self.receiver.close()
+    }
+}
+
+impl<S> LocalContext<S>
+where
+    S: LocalService,
+{
+    /// Start an service on current `LocalSet`
+    pub fn run(self, service: S) -> LocalAddress<S> {
+        let mut this = self;
+
+        let address = this.addr();
+
+        let mut service = service;
+
+        tokio::task::spawn_local(async move {
+            service.started(&mut this).await;
+            while let Some(mut e) = this.receiver.recv().await {
+                e.0.handle(&mut service, &mut this).await;
+            }
+            service.stopped(&mut this).await;
+        });
+
+        address
+    }
+}
+
+/// Address of local service
+///
+/// This address can clone, but can't send to other thread.
+pub struct LocalAddress<S> {
+    sender: UnboundedSender<LocalEnvelope<S>>,
+}
+
+impl<S> Clone for LocalAddress<S> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<S> LocalAddress<S> {
+    /// Return true when service stopped.
+    pub fn is_stop(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
+
+impl<S> LocalAddress<S>
+where
+    S: LocalService,
+{
+    /// Call service's handler and get result
+    pub async fn call<M>(&self, message: M) -> Result<M::Result>
+    where
+        M: Message + 'static,
+        S: LocalHandler<M>,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        let env = LocalEnvelope::new(message, Some(sender));
+
+        self.sender.send(env)?;
+
+        Ok(receiver.await?)
+    }
+
+    /// Call service's handler without result
+    pub fn send<M>(&self, message: M) -> Result<()>
+    where
+        M: Message + 'static,
+        S: LocalHandler<M>,
+    {
+        let env = LocalEnvelope::new(message, None);
+
+        self.sender.send(env)?;
+
+        Ok(())
+    }
+}
+
+struct LocalEnvelope<S>(Box<dyn LocalEnvelopProxy<S>>);
+
+impl<S> LocalEnvelope<S>
+where
+    S: LocalService,
+{
+    fn new<M>(message: M, result_channel: Option<oneshot::Sender<M::Result>>) -> Self
+    where
+        S: LocalHandler<M>,
+        M: Message + 'static,
+    {
+        Self(Box::new(LocalEnvelopWithMessage {
+            message: Some(message),
+            result_channel,
+        }))
+    }
+}
+
+#[async_trait(?Send)]
+trait LocalEnvelopProxy<S> {
+    async fn handle(&mut self, svc: &mut S, ctx: &mut LocalContext<S>);
+}
+
+struct LocalEnvelopWithMessage<M>
+where
+    M: Message,
+{
+    message: Option<M>,
+    result_channel: Option<oneshot::Sender<M::Result>>,
+}
+
+#[async_trait(?Send)]
+impl<S, M> LocalEnvelopProxy<S> for LocalEnvelopWithMessage<M>
+where
+    M: Message,
+    S: LocalHandler<M>,
+{
+    async fn handle(&mut self, svc: &mut S, ctx: &mut LocalContext<S>) {
+        if let Some(message) = self.message.take() {
+            let res = <S as LocalHandler<M>>::handler(svc, message, ctx).await;
+
+            if let Some(rc) = self.result_channel.take() {
+                if rc.send(res).is_err() {
+                    log::warn!("Channel Closed");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::{Context, ServiceId};
+
+/// Metadata of message handling
+#[derive(Debug, Clone)]
+pub struct MessageMeta {
+    /// Id of service
+    pub service: ServiceId,
+    /// Type name of message
+    pub message_type: &'static str,
+    /// Deadline of call, only have value when sent by `Address::call_timeout`
+    pub deadline: Option<Instant>,
+}
+
+/// Decision of middleware
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Continue to next middleware and handler
+    Continue,
+    /// Drop this message, handler will not be called
+    ///
+    /// If message sent by `call`, caller will get `Canceled`.
+    Skip,
+}
+
+/// Middleware run around every message handled by service
+///
+/// Middlewares run by the order of `Context::add_middleware` before handler,
+/// and reverse order after handler.
+#[async_trait]
+pub trait Middleware<S>: Send + 'static {
+    /// Called before handler
+    async fn before(&mut self, _meta: &MessageMeta, _ctx: &mut Context<S>) -> Decision {
+        Decision::Continue
+    }
+
+    /// Called after handler, only when handler called
+    async fn after(&mut self, _meta: &MessageMeta, _ctx: &mut Context<S>) {}
+}
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
+use tokio::sync::oneshot;
 
-use crate::{Address, Context};
+use crate::{Address, Context, Envelope, InitError, Result};
 
 /// A service is an running like thread
 #[async_trait]
@@ -13,9 +14,75 @@ pub trait Service: Send + Sized + 'static {
         ctx.run(self)
     }
 
+    /// Start service, and get a receiver to known `init` succeeded or not
+    fn start_checked(self) -> (Address<Self>, oneshot::Receiver<Result<()>>) {
+        Context::new().run_checked(self)
+    }
+
+    /// Hook for service initialize
+    ///
+    /// This hook called before `started`. If an error returned, service will
+    /// call `stopped` and stop immediately without handle any message.
+    async fn init(&mut self, _ctx: &mut Context<Self>) -> std::result::Result<(), InitError> {
+        Ok(())
+    }
+
     /// Hook for service started
     async fn started(&mut self, _ctx: &mut Context<Self>) {}
 
+    /// Handle a batch of messages
+    ///
+    /// Only called when `Context::set_batch_size` set. By default, messages are
+    /// dispatched one by one. Override it to coalesce work like database writes,
+    /// use `Context::dispatch` to handle each of them.
+    ///
+    /// `Context::replace_service` only takes effect after whole batch handled.
+    async fn handle_batch(&mut self, batch: Vec<Envelope<Self>>, ctx: &mut Context<Self>) {
+        for e in batch {
+            ctx.dispatch(self, e).await;
+        }
+    }
+
+    /// Hook for caller dropped before result sent
+    ///
+    /// Called after handler finished but caller already gave up, like timeout.
+    /// The result is dropped. Useful to record metrics of abandoned calls.
+    async fn call_abandoned(&mut self, _message_type: &'static str, _ctx: &mut Context<Self>) {}
+
+    /// Hook for mailbox become empty
+    ///
+    /// Called once each time all pending messages have been handled.
+    async fn idle(&mut self, _ctx: &mut Context<Self>) {}
+
+    /// Hook for all addresses of service dropped
+    ///
+    /// Called after pending messages have been handled. Service keep running
+    /// by default, call `ctx.stop()` here to stop it.
+    async fn last_address_dropped(&mut self, _ctx: &mut Context<Self>) {}
+
+    /// Hook for messages not handled when service stopped
+    ///
+    /// Called before `stopped` with remaining envelopes in mailbox. They are
+    /// dropped by default, so callers get `Canceled`. Use `Envelope::reply` to
+    /// answer them with a default value.
+    async fn on_stop_drain(&mut self, _pending: Vec<Envelope<Self>>, _ctx: &mut Context<Self>) {}
+
     /// Hook for service stopped
     async fn stopped(&mut self, _ctx: &mut Context<Self>) {}
 }
+
+/// Service need an argument when start
+///
+/// Use it to pass resources only available at spawn time, like a connection pool.
+#[async_trait]
+pub trait StartWith<A>: Service
+where
+    A: Send + 'static,
+{
+    fn start_with(self, args: A) -> Address<Self> {
+        Context::new().run_with(self, args)
+    }
+
+    /// Hook for service started with argument, called before `started`
+    async fn started_with(&mut self, args: A, ctx: &mut Context<Self>);
+}
@@ -0,0 +1,134 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::Instant,
+};
+
+use tokio::sync::Notify;
+
+use crate::{Error, Result, ServiceId, Timer, TokioTimer};
+
+/// State shared by service's context and addresses
+pub(crate) struct ServiceState {
+    pub id: ServiceId,
+    timer: RwLock<Arc<dyn Timer>>,
+    addresses: AtomicUsize,
+    last_address_dropped: Notify,
+    stop_requested: AtomicBool,
+    stop_requested_at: Mutex<Option<Instant>>,
+    stop: Notify,
+    ready: AtomicBool,
+    exited: AtomicBool,
+    lifecycle: Notify,
+}
+
+impl Default for ServiceState {
+    fn default() -> Self {
+        Self {
+            id: ServiceId::next(),
+            timer: RwLock::new(Arc::new(TokioTimer)),
+            addresses: Default::default(),
+            last_address_dropped: Default::default(),
+            stop_requested: Default::default(),
+            stop_requested_at: Default::default(),
+            stop: Default::default(),
+            ready: Default::default(),
+            exited: Default::default(),
+            lifecycle: Default::default(),
+        }
+    }
+}
+
+impl ServiceState {
+    pub fn timer(&self) -> Arc<dyn Timer> {
+        self.timer.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set_timer(&self, timer: Arc<dyn Timer>) {
+        *self.timer.write().unwrap_or_else(|e| e.into_inner()) = timer;
+    }
+
+    pub fn address_created(&self) {
+        self.addresses.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub fn address_dropped(&self) {
+        if self.addresses.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.last_address_dropped.notify_one();
+        }
+    }
+
+    pub fn address_count(&self) -> usize {
+        self.addresses.load(Ordering::Acquire)
+    }
+
+    /// Wait until address count become zero
+    ///
+    /// This may return spuriously, please check `address_count` again.
+    pub async fn last_address_dropped(&self) {
+        self.last_address_dropped.notified().await
+    }
+
+    pub fn request_stop(&self) {
+        let now = self.timer().now();
+        self.stop_requested_at
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert(now);
+
+        self.stop_requested.store(true, Ordering::Release);
+        self.stop.notify_one();
+    }
+
+    pub fn is_stop_requested(&self) -> bool {
+        self.stop_requested.load(Ordering::Acquire)
+    }
+
+    /// Time of first stop request
+    pub fn stop_requested_at(&self) -> Option<Instant> {
+        *self
+            .stop_requested_at
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait until stop requested
+    pub async fn stop_requested(&self) {
+        self.stop.notified().await
+    }
+
+    /// Mark `started` hook finished
+    pub fn set_ready(&self) {
+        self.ready.store(true, Ordering::Release);
+        self.lifecycle.notify_waiters();
+    }
+
+    /// Mark run loop exited
+    pub fn set_exited(&self) {
+        self.exited.store(true, Ordering::Release);
+        self.lifecycle.notify_waiters();
+    }
+
+    /// Wait until `started` hook finished
+    ///
+    /// Return `ServiceStoped` if service exited before ready.
+    pub async fn wait_ready(&self) -> Result<()> {
+        loop {
+            let notified = self.lifecycle.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if self.ready.load(Ordering::Acquire) {
+                return Ok(());
+            }
+
+            if self.exited.load(Ordering::Acquire) {
+                return Err(Error::ServiceStoped);
+            }
+
+            notified.await;
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use crate::ServiceState;
+
+/// Handle to stop service from outside
+///
+/// This handle can clone.
+#[derive(Clone)]
+pub struct StopHandle {
+    pub(crate) state: Arc<ServiceState>,
+}
+
+impl StopHandle {
+    /// Stop service
+    ///
+    /// Service will stop after current message handled, and call `stopped` hook.
+    /// Messages still in mailbox will be dropped, caller will get `Canceled`.
+    /// If service started by `Context::run_with_shutdown`, messages are drained
+    /// until drain timeout instead.
+    pub fn stop(&self) {
+        self.state.request_stop();
+    }
+
+    /// Return true when stop already requested.
+    pub fn is_stop_requested(&self) -> bool {
+        self.state.is_stop_requested()
+    }
+}
@@ -0,0 +1,63 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{Context, Envelope};
+
+/// Context to test handler without start service
+///
+/// Pass it to handler directly, like `svc.handler(msg, &mut ctx).await`.
+/// Messages sent to service itself, like by `ctx.notify`, are kept and can
+/// be read by `pending`.
+pub struct TestContext<S> {
+    ctx: Context<S>,
+}
+
+impl<S> Default for TestContext<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> TestContext<S> {
+    /// Create an empty test context
+    pub fn new() -> Self {
+        Self {
+            ctx: Context::new(),
+        }
+    }
+
+    /// Take all messages sent to service
+    pub fn pending(&mut self) -> Vec<Envelope<S>> {
+        let mut res = Vec::new();
+
+        while let Ok(e) = self.ctx.receiver.try_recv() {
+            res.push(e);
+        }
+
+        res
+    }
+
+    /// Take all messages with type `M`, other messages are dropped
+    pub fn pending_messages<M>(&mut self) -> Vec<M>
+    where
+        M: 'static,
+    {
+        self.pending()
+            .into_iter()
+            .filter_map(|e| e.into_message())
+            .collect()
+    }
+}
+
+impl<S> Deref for TestContext<S> {
+    type Target = Context<S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.ctx
+    }
+}
+
+impl<S> DerefMut for TestContext<S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.ctx
+    }
+}
@@ -0,0 +1,33 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+/// Timer used by time based features
+///
+/// Context hold a timer, all features need time should use it.
+#[async_trait]
+pub trait Timer: Send + Sync + 'static {
+    /// Sleep for duration
+    async fn sleep(&self, duration: Duration);
+
+    /// Get current time
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Timer backed by tokio
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioTimer;
+
+#[async_trait]
+impl Timer for TokioTimer {
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await
+    }
+
+    /// Use tokio's clock, so it follows paused time in tests.
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+}
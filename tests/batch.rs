@@ -0,0 +1,72 @@
+mod common;
+
+use async_trait::async_trait;
+use common::Counter;
+use serviceless::{Context, Envelope, Handler, Message, Service};
+
+#[derive(Default)]
+struct Batcher {
+    batches: Vec<usize>,
+}
+
+#[async_trait]
+impl Service for Batcher {
+    async fn handle_batch(&mut self, batch: Vec<Envelope<Self>>, ctx: &mut Context<Self>) {
+        self.batches.push(batch.len());
+
+        for e in batch {
+            ctx.dispatch(self, e).await;
+        }
+    }
+}
+
+struct Echo(u64);
+
+impl Message for Echo {
+    type Result = u64;
+}
+
+struct Batches;
+
+impl Message for Batches {
+    type Result = Vec<usize>;
+}
+
+#[async_trait]
+impl Handler<Echo> for Batcher {
+    async fn handler(&mut self, message: Echo, _ctx: &mut Context<Self>) -> u64 {
+        message.0
+    }
+}
+
+#[async_trait]
+impl Handler<Batches> for Batcher {
+    async fn handler(&mut self, _message: Batches, _ctx: &mut Context<Self>) -> Vec<usize> {
+        self.batches.clone()
+    }
+}
+
+#[tokio::test]
+async fn handle_in_batch() {
+    let count = Counter::default();
+
+    let mut ctx = Context::new();
+    ctx.set_batch_size(3);
+    ctx.add_middleware(count.clone());
+
+    let addr = Batcher::default().start_by_context(ctx);
+
+    let results: Vec<_> = addr
+        .call_many((0..5).map(Echo).collect())
+        .await
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(results, vec![0, 1, 2, 3, 4]);
+
+    // Last batch is `Batches` itself.
+    assert_eq!(addr.call(Batches).await.unwrap(), vec![3, 2, 1]);
+
+    // Middleware applied on every message by `Context::dispatch`.
+    assert_eq!(count.count(), 6);
+}
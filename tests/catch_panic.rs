@@ -0,0 +1,59 @@
+mod common;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use common::Counter;
+use serviceless::{Context, Error, Handler, Message, Service};
+
+struct Fragile;
+
+impl Service for Fragile {}
+
+struct Boom(Counter);
+
+impl Message for Boom {
+    type Result = ();
+}
+
+struct Peek;
+
+impl Message for Peek {
+    type Result = bool;
+}
+
+#[async_trait]
+impl Handler<Boom> for Fragile {
+    async fn handler(&mut self, message: Boom, ctx: &mut Context<Self>) {
+        ctx.add_middleware(message.0);
+        panic!("boom");
+    }
+}
+
+#[async_trait]
+impl Handler<Peek> for Fragile {
+    async fn handler(&mut self, _message: Peek, ctx: &mut Context<Self>) -> bool {
+        ctx.deadline().is_some()
+    }
+}
+
+#[tokio::test]
+async fn panic_is_caught() {
+    let mut ctx = Context::new();
+    ctx.set_catch_panic(true);
+    let addr = Fragile.start_by_context(ctx);
+
+    let count = Counter::default();
+
+    let res = addr
+        .call_timeout(Boom(count.clone()), Duration::from_secs(60))
+        .await;
+    assert!(matches!(res, Err(Error::Canceled)));
+
+    // Deadline of panicked call is reset.
+    assert!(!addr.call(Peek).await.unwrap());
+
+    // Middleware added before panic is kept.
+    assert_eq!(count.count(), 1);
+    assert!(!addr.is_stop());
+}
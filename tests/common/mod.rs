@@ -0,0 +1,25 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use async_trait::async_trait;
+use serviceless::{Context, Decision, MessageMeta, Middleware};
+
+/// Middleware count messages passed to handler
+#[derive(Clone, Default)]
+pub struct Counter(Arc<AtomicUsize>);
+
+impl Counter {
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl<S: Send + 'static> Middleware<S> for Counter {
+    async fn before(&mut self, _meta: &MessageMeta, _ctx: &mut Context<S>) -> Decision {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Decision::Continue
+    }
+}
@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use serviceless::{Address, CallError, Context, Error, Handler, Message, Service};
+
+struct Caller;
+
+impl Service for Caller {}
+
+struct CallSelf;
+
+impl Message for CallSelf {
+    type Result = Result<(), Error>;
+}
+
+struct TryCallSelf;
+
+impl Message for TryCallSelf {
+    type Result = bool;
+}
+
+struct NotifyCallSelf;
+
+impl Message for NotifyCallSelf {
+    type Result = Result<(), Error>;
+}
+
+struct Echo;
+
+impl Message for Echo {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<CallSelf> for Caller {
+    async fn handler(&mut self, _message: CallSelf, ctx: &mut Context<Self>) -> Result<(), Error> {
+        ctx.addr().call(Echo).await
+    }
+}
+
+#[async_trait]
+impl Handler<TryCallSelf> for Caller {
+    async fn handler(&mut self, _message: TryCallSelf, ctx: &mut Context<Self>) -> bool {
+        matches!(
+            ctx.addr().try_call(Echo).await,
+            Err(CallError::WouldDeadlock(Echo))
+        )
+    }
+}
+
+#[async_trait]
+impl Handler<NotifyCallSelf> for Caller {
+    async fn handler(
+        &mut self,
+        _message: NotifyCallSelf,
+        ctx: &mut Context<Self>,
+    ) -> Result<(), Error> {
+        ctx.notify_call(Echo).await
+    }
+}
+
+#[async_trait]
+impl Handler<Echo> for Caller {
+    async fn handler(&mut self, _message: Echo, _ctx: &mut Context<Self>) {}
+}
+
+struct Relay(Address<Caller>);
+
+impl Service for Relay {}
+
+#[async_trait]
+impl Handler<CallSelf> for Relay {
+    async fn handler(&mut self, message: CallSelf, _ctx: &mut Context<Self>) -> Result<(), Error> {
+        self.0.call(message).await?
+    }
+}
+
+struct CallRelay(Address<Relay>);
+
+impl Message for CallRelay {
+    type Result = Result<(), Error>;
+}
+
+#[async_trait]
+impl Handler<CallRelay> for Caller {
+    async fn handler(&mut self, message: CallRelay, _ctx: &mut Context<Self>) -> Result<(), Error> {
+        message.0.call(CallSelf).await?
+    }
+}
+
+#[tokio::test]
+async fn call_self() {
+    let addr = Caller.start();
+
+    let res = addr.call(CallSelf).await.unwrap();
+    assert!(matches!(res, Err(Error::WouldDeadlock)));
+}
+
+#[tokio::test]
+async fn try_call_self() {
+    let addr = Caller.start();
+
+    assert!(addr.call(TryCallSelf).await.unwrap());
+}
+
+#[tokio::test]
+async fn notify_call_self() {
+    let addr = Caller.start();
+
+    let res = addr.call(NotifyCallSelf).await.unwrap();
+    assert!(matches!(res, Err(Error::WouldDeadlock)));
+}
+
+#[tokio::test]
+async fn call_cycle() {
+    let caller = Caller.start();
+    let relay = Relay(caller.clone()).start();
+
+    // Caller -> Relay -> Caller
+    let res = caller.call(CallRelay(relay)).await.unwrap();
+    assert!(matches!(res, Err(Error::WouldDeadlock)));
+}
+
+#[tokio::test]
+async fn call_outside_handler() {
+    let addr = Caller.start();
+
+    addr.call(Echo).await.unwrap();
+}
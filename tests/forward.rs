@@ -0,0 +1,131 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    any::TypeId,
+    cell::Cell,
+};
+
+use async_trait::async_trait;
+use serviceless::{Address, Context, Envelope, ForwardError, Handler, Message, Service};
+
+/// Count allocations of current thread
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+struct Query(u64);
+
+impl Message for Query {
+    type Result = u64;
+}
+
+struct Other;
+
+impl Message for Other {
+    type Result = ();
+}
+
+struct Back;
+
+impl Service for Back {}
+
+#[async_trait]
+impl Handler<Query> for Back {
+    async fn handler(&mut self, message: Query, _ctx: &mut Context<Self>) -> u64 {
+        message.0 * 2
+    }
+}
+
+#[async_trait]
+impl Handler<Other> for Back {
+    async fn handler(&mut self, _message: Other, _ctx: &mut Context<Self>) {}
+}
+
+struct Front {
+    back: Address<Back>,
+    allocations: Vec<usize>,
+}
+
+#[async_trait]
+impl Service for Front {
+    async fn handle_batch(&mut self, batch: Vec<Envelope<Self>>, ctx: &mut Context<Self>) {
+        for e in batch {
+            if e.message_type_id() != TypeId::of::<Query>() {
+                ctx.dispatch(self, e).await;
+                continue;
+            }
+
+            // Wrong message type, envelope returned.
+            let e = match ctx.forward::<Other, Back>(e, &self.back) {
+                Err(ForwardError::MessageMismatch(e)) => e,
+                _ => panic!("message must mismatch"),
+            };
+
+            let before = allocations();
+            ctx.forward::<Query, Back>(e, &self.back).unwrap();
+            self.allocations.push(allocations() - before);
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Query> for Front {
+    async fn handler(&mut self, _message: Query, _ctx: &mut Context<Self>) -> u64 {
+        unreachable!("query is forwarded")
+    }
+}
+
+struct Allocations;
+
+impl Message for Allocations {
+    type Result = Vec<usize>;
+}
+
+#[async_trait]
+impl Handler<Allocations> for Front {
+    async fn handler(&mut self, _message: Allocations, _ctx: &mut Context<Self>) -> Vec<usize> {
+        self.allocations.clone()
+    }
+}
+
+#[tokio::test]
+async fn forward_call_to_other_service() {
+    let before = allocations();
+    std::hint::black_box(Box::new(0u64));
+    assert!(allocations() > before, "allocations must be counted");
+
+    let back = Back.start();
+
+    let mut ctx = Context::new();
+    ctx.set_batch_size(1);
+    let front = Front {
+        back,
+        allocations: Vec::new(),
+    }
+    .start_by_context(ctx);
+
+    // Caller get result from `Back`.
+    assert_eq!(front.call(Query(21)).await.unwrap(), 42);
+
+    // Envelope moved without re-allocation.
+    assert_eq!(front.call(Allocations).await.unwrap(), vec![0]);
+}
@@ -0,0 +1,167 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use serviceless::{Context, Error, Handler, InitError, Message, Service};
+use tokio::sync::oneshot;
+
+struct PanicOnStart;
+
+#[async_trait]
+impl Service for PanicOnStart {
+    async fn started(&mut self, _ctx: &mut Context<Self>) {
+        panic!("start failed");
+    }
+}
+
+#[tokio::test]
+async fn wait_ready_when_started_panicked() {
+    let addr = PanicOnStart.start();
+
+    let res = tokio::time::timeout(Duration::from_secs(5), addr.wait_ready())
+        .await
+        .expect("wait_ready must resolve");
+
+    assert!(matches!(res, Err(Error::ServiceStoped)));
+}
+
+struct SlowStart;
+
+#[async_trait]
+impl Service for SlowStart {
+    async fn started(&mut self, _ctx: &mut Context<Self>) {
+        std::future::pending::<()>().await;
+    }
+}
+
+#[tokio::test]
+async fn wait_ready_when_aborted() {
+    let (addr, handle) = Context::new().run_fallible(SlowStart);
+
+    handle.abort();
+
+    let res = tokio::time::timeout(Duration::from_secs(5), addr.wait_ready())
+        .await
+        .expect("wait_ready must resolve");
+
+    assert!(matches!(res, Err(Error::ServiceStoped)));
+}
+
+struct Failing;
+
+impl Service for Failing {}
+
+struct Fail;
+
+impl Message for Fail {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Fail> for Failing {
+    async fn handler(&mut self, _message: Fail, ctx: &mut Context<Self>) {
+        ctx.fail("fatal");
+    }
+}
+
+#[tokio::test]
+async fn run_fallible_returns_failure() {
+    let (addr, handle) = Context::new().run_fallible(Failing);
+
+    addr.call(Fail).await.unwrap();
+
+    match handle.await.unwrap() {
+        Err(Error::Failed(e)) => assert_eq!(e.to_string(), "fatal"),
+        res => panic!("unexpected result: {res:?}"),
+    }
+}
+
+struct InitFail {
+    stopped: Option<oneshot::Sender<()>>,
+}
+
+#[async_trait]
+impl Service for InitFail {
+    async fn init(&mut self, _ctx: &mut Context<Self>) -> Result<(), InitError> {
+        Err("no database".into())
+    }
+
+    async fn stopped(&mut self, _ctx: &mut Context<Self>) {
+        if let Some(sender) = self.stopped.take() {
+            let _ = sender.send(());
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Fail> for InitFail {
+    async fn handler(&mut self, _message: Fail, _ctx: &mut Context<Self>) {
+        unreachable!("no message handled after init failed")
+    }
+}
+
+#[tokio::test]
+async fn init_failed() {
+    let (sender, stopped) = oneshot::channel();
+    let (addr, init) = InitFail {
+        stopped: Some(sender),
+    }
+    .start_checked();
+
+    // Call enqueued before `init` runs.
+    let (init, queued) = tokio::join!(init, addr.call(Fail));
+
+    assert!(matches!(init.unwrap(), Err(Error::InitFailed(_))));
+    assert!(matches!(queued, Err(Error::Canceled)));
+    assert!(matches!(addr.wait_ready().await, Err(Error::ServiceStoped)));
+
+    stopped.await.expect("stopped must be called");
+}
+
+type Log = Arc<Mutex<Vec<&'static str>>>;
+
+struct Logged(Log);
+
+#[async_trait]
+impl Service for Logged {
+    async fn stopped(&mut self, _ctx: &mut Context<Self>) {
+        self.0.lock().unwrap().push("stopped");
+    }
+}
+
+fn logged_context(log: &Log) -> Context<Logged> {
+    let mut ctx = Context::new();
+    let log = log.clone();
+    ctx.on_drop(move || log.lock().unwrap().push("on_drop"));
+    ctx
+}
+
+#[tokio::test]
+async fn on_drop_after_stopped() {
+    let log = Log::default();
+
+    let ctx = logged_context(&log);
+    let stop_handle = ctx.stop_handle();
+
+    let (_addr, handle) = ctx.run_fallible(Logged(log.clone()));
+
+    stop_handle.stop();
+    handle.await.unwrap().unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec!["stopped", "on_drop"]);
+}
+
+#[tokio::test]
+async fn on_drop_when_aborted() {
+    let log = Log::default();
+
+    let (_addr, handle) = logged_context(&log).run_fallible(Logged(log.clone()));
+
+    handle.abort();
+    assert!(handle.await.unwrap_err().is_cancelled());
+
+    // `stopped` can't run when aborted, but callback still runs.
+    assert_eq!(*log.lock().unwrap(), vec!["on_drop"]);
+}
@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use serviceless::{Context, Handler, Message, Service};
+
+struct Custom;
+
+impl Service for Custom {}
+
+struct Job(u64);
+
+impl Message for Job {
+    type Result = u64;
+}
+
+#[async_trait]
+impl Handler<Job> for Custom {
+    async fn handler(&mut self, message: Job, ctx: &mut Context<Self>) -> u64 {
+        if message.0 == 0 {
+            ctx.stop();
+        }
+
+        message.0
+    }
+}
+
+#[tokio::test]
+async fn custom_loop_stop_from_handler() {
+    let ctx = Context::new();
+    let addr = ctx.addr();
+    let (mut ctx, mut receiver) = ctx.into_parts();
+
+    let task = tokio::spawn(async move {
+        let mut service = Custom;
+        let mut handled = 0;
+
+        while let Some(mut e) = receiver.recv().await {
+            e.handle(&mut service, &mut ctx).await;
+            handled += 1;
+
+            if ctx.is_stop_requested() {
+                receiver.close();
+            }
+        }
+
+        handled
+    });
+
+    let results = addr.call_many((0..3).map(Job).collect()).await;
+    assert!(results.into_iter().all(|r| r.is_ok()));
+
+    // Loop ended after mailbox closed and drained.
+    assert_eq!(task.await.unwrap(), 3);
+    assert!(addr.is_stop());
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use serviceless::{Context, Handler, Message, Service};
+
+#[derive(Default)]
+struct Recorder {
+    order: Vec<u64>,
+}
+
+impl Service for Recorder {}
+
+struct Record(u64);
+
+impl Message for Record {
+    type Result = ();
+}
+
+struct Order;
+
+impl Message for Order {
+    type Result = Vec<u64>;
+}
+
+#[async_trait]
+impl Handler<Record> for Recorder {
+    async fn handler(&mut self, message: Record, _ctx: &mut Context<Self>) {
+        self.order.push(message.0);
+    }
+}
+
+#[async_trait]
+impl Handler<Order> for Recorder {
+    async fn handler(&mut self, _message: Order, _ctx: &mut Context<Self>) -> Vec<u64> {
+        self.order.clone()
+    }
+}
+
+#[tokio::test]
+async fn higher_level_first() {
+    let mut ctx = Context::new();
+    ctx.priority(3);
+
+    let addr = Recorder::default().start_by_context(ctx);
+
+    // Service not polled yet, all messages are pending together.
+    addr.send_with_priority(Record(10), 0).unwrap();
+    addr.send_with_priority(Record(11), 0).unwrap();
+    addr.send_with_priority(Record(20), 1).unwrap();
+    addr.send_with_priority(Record(30), 9).unwrap();
+    addr.send_with_priority(Record(21), 1).unwrap();
+
+    assert_eq!(addr.call(Order).await.unwrap(), vec![30, 20, 21, 10, 11]);
+}
+
+#[tokio::test]
+async fn fifo_without_priority() {
+    let addr = Recorder::default().start();
+
+    addr.send_with_priority(Record(0), 0).unwrap();
+    addr.send_with_priority(Record(1), 2).unwrap();
+    addr.send_with_priority(Record(2), 1).unwrap();
+
+    assert_eq!(addr.call(Order).await.unwrap(), vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn higher_level_first_after_idle() {
+    let mut ctx = Context::new();
+    ctx.priority(3);
+
+    let addr = Recorder::default().start_by_context(ctx);
+
+    // Let service become idle and wait on its mailbox.
+    addr.call(Order).await.unwrap();
+
+    addr.send_with_priority(Record(0), 0).unwrap();
+    addr.send_with_priority(Record(1), 0).unwrap();
+    addr.send_with_priority(Record(2), 2).unwrap();
+
+    assert_eq!(addr.call(Order).await.unwrap(), vec![2, 0, 1]);
+}
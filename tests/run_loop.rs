@@ -0,0 +1,110 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+
+use async_trait::async_trait;
+use serviceless::{Context, Handler, Message, Service};
+use tokio::sync::oneshot;
+
+#[derive(Default)]
+struct Idle {
+    idle: usize,
+}
+
+#[async_trait]
+impl Service for Idle {
+    async fn idle(&mut self, _ctx: &mut Context<Self>) {
+        self.idle += 1;
+    }
+}
+
+struct Ping;
+
+impl Message for Ping {
+    type Result = ();
+}
+
+struct IdleCount;
+
+impl Message for IdleCount {
+    type Result = usize;
+}
+
+#[async_trait]
+impl Handler<Ping> for Idle {
+    async fn handler(&mut self, _message: Ping, _ctx: &mut Context<Self>) {}
+}
+
+#[async_trait]
+impl Handler<IdleCount> for Idle {
+    async fn handler(&mut self, _message: IdleCount, _ctx: &mut Context<Self>) -> usize {
+        self.idle
+    }
+}
+
+#[tokio::test]
+async fn idle_after_mailbox_empty() {
+    let addr = Idle::default().start();
+
+    for _ in 0..3 {
+        addr.send(Ping).unwrap();
+    }
+
+    // Messages handled back-to-back, no idle between them.
+    assert_eq!(addr.call(IdleCount).await.unwrap(), 0);
+    assert_eq!(addr.call(IdleCount).await.unwrap(), 1);
+}
+
+struct Unreferenced {
+    handled: Arc<AtomicUsize>,
+    dropped: Arc<AtomicBool>,
+    stopped: Option<oneshot::Sender<()>>,
+}
+
+#[async_trait]
+impl Service for Unreferenced {
+    async fn last_address_dropped(&mut self, _ctx: &mut Context<Self>) {
+        self.dropped.store(true, Ordering::SeqCst);
+    }
+
+    async fn stopped(&mut self, _ctx: &mut Context<Self>) {
+        if let Some(sender) = self.stopped.take() {
+            let _ = sender.send(());
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Ping> for Unreferenced {
+    async fn handler(&mut self, _message: Ping, _ctx: &mut Context<Self>) {
+        self.handled.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn stop_when_last_address_dropped() {
+    let handled = Arc::new(AtomicUsize::new(0));
+    let dropped = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = oneshot::channel();
+
+    let mut ctx = Context::new();
+    ctx.set_stop_when_unreferenced(true);
+
+    let addr = Unreferenced {
+        handled: handled.clone(),
+        dropped: dropped.clone(),
+        stopped: Some(sender),
+    }
+    .start_by_context(ctx);
+
+    addr.send(Ping).unwrap();
+    addr.send(Ping).unwrap();
+    drop(addr);
+
+    receiver.await.unwrap();
+
+    // Messages in mailbox are handled before hook.
+    assert_eq!(handled.load(Ordering::SeqCst), 2);
+    assert!(dropped.load(Ordering::SeqCst));
+}
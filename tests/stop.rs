@@ -0,0 +1,168 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use serviceless::{Context, Envelope, Error, Handler, Message, Service, StopHandle};
+
+struct Worker {
+    stop_handle: Option<StopHandle>,
+}
+
+impl Service for Worker {}
+
+/// Sleep, then stop service if id is 0
+struct Job(u64);
+
+impl Message for Job {
+    type Result = u64;
+}
+
+#[async_trait]
+impl Handler<Job> for Worker {
+    async fn handler(&mut self, message: Job, ctx: &mut Context<Self>) -> u64 {
+        tokio::time::sleep(Duration::from_millis(400)).await;
+
+        if message.0 == 0 {
+            match self.stop_handle.take() {
+                Some(handle) => handle.stop(),
+                None => ctx.stop(),
+            }
+        }
+
+        message.0
+    }
+}
+
+fn jobs() -> Vec<Job> {
+    (0..5).map(Job).collect()
+}
+
+/// Every caller must resolve, either handled or canceled.
+fn collect(results: Vec<serviceless::Result<u64>>) -> (usize, usize) {
+    let mut handled = 0;
+    let mut canceled = 0;
+
+    for (i, res) in results.into_iter().enumerate() {
+        match res {
+            Ok(v) => {
+                assert_eq!(v, i as u64);
+                handled += 1;
+            }
+            Err(Error::Canceled) => canceled += 1,
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    (handled, canceled)
+}
+
+async fn call_all(addr: &serviceless::Address<Worker>) -> (usize, usize) {
+    let results = tokio::time::timeout(Duration::from_secs(60), addr.call_many(jobs()))
+        .await
+        .expect("pending calls must not hang");
+
+    collect(results)
+}
+
+#[tokio::test(start_paused = true)]
+async fn stop_by_context_handles_queued() {
+    let addr = Worker { stop_handle: None }.start();
+
+    assert_eq!(call_all(&addr).await, (5, 0));
+    assert!(matches!(addr.call(Job(9)).await, Err(Error::ServiceStoped)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stop_by_handle_cancels_queued() {
+    let ctx = Context::new();
+    let stop_handle = Some(ctx.stop_handle());
+    let addr = Worker { stop_handle }.start_by_context(ctx);
+
+    assert_eq!(call_all(&addr).await, (1, 4));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stop_with_drain_timeout() {
+    let addr =
+        Context::new().run_with_shutdown(Worker { stop_handle: None }, Duration::from_millis(1000));
+
+    // Stopped at 0.4s, drain until 1.4s, job 4 starts at 1.6s.
+    assert_eq!(call_all(&addr).await, (4, 1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stop_with_max_drain_timeout() {
+    let addr = Context::new().run_with_shutdown(Worker { stop_handle: None }, Duration::MAX);
+
+    assert_eq!(call_all(&addr).await, (5, 0));
+}
+
+struct Drainer {
+    stop_handle: Option<StopHandle>,
+    mismatched: Arc<AtomicUsize>,
+}
+
+struct Other;
+
+impl Message for Other {
+    type Result = ();
+}
+
+#[async_trait]
+impl Service for Drainer {
+    async fn on_stop_drain(&mut self, pending: Vec<Envelope<Self>>, _ctx: &mut Context<Self>) {
+        for e in pending {
+            // Wrong message type, envelope returned.
+            let e = match e.reply::<Other>(()) {
+                Err(e) => e,
+                Ok(()) => panic!("message must mismatch"),
+            };
+            self.mismatched.fetch_add(1, Ordering::SeqCst);
+
+            if e.reply::<Job>(99).is_err() {
+                panic!("message must be `Job`");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Job> for Drainer {
+    async fn handler(&mut self, message: Job, _ctx: &mut Context<Self>) -> u64 {
+        tokio::time::sleep(Duration::from_millis(400)).await;
+
+        if let Some(handle) = self.stop_handle.take() {
+            handle.stop();
+        }
+
+        message.0
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn reply_pending_on_stop() {
+    let ctx = Context::new();
+    let stop_handle = Some(ctx.stop_handle());
+    let mismatched = Arc::new(AtomicUsize::new(0));
+    let addr = Drainer {
+        stop_handle,
+        mismatched: mismatched.clone(),
+    }
+    .start_by_context(ctx);
+
+    let results: Vec<_> = addr
+        .call_many(jobs())
+        .await
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+
+    // First job handled, others answered by `on_stop_drain`.
+    assert_eq!(results, vec![0, 99, 99, 99, 99]);
+    assert_eq!(mismatched.load(Ordering::SeqCst), 4);
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serviceless::{Context, Handler, Message, Service};
+
+struct Sleeper;
+
+impl Service for Sleeper {}
+
+struct Remaining;
+
+impl Message for Remaining {
+    type Result = Option<Duration>;
+}
+
+#[async_trait]
+impl Handler<Remaining> for Sleeper {
+    async fn handler(&mut self, _message: Remaining, ctx: &mut Context<Self>) -> Option<Duration> {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        ctx.time_remaining()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn time_remaining_follows_paused_clock() {
+    let addr = Sleeper.start();
+
+    let remaining = addr
+        .call_timeout(Remaining, Duration::from_secs(10))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert!(remaining <= Duration::from_secs(5));
+    assert!(remaining > Duration::from_secs(4));
+}
+
+struct Deadline;
+
+impl Message for Deadline {
+    type Result = Option<Duration>;
+}
+
+#[async_trait]
+impl Handler<Deadline> for Sleeper {
+    async fn handler(&mut self, _message: Deadline, ctx: &mut Context<Self>) -> Option<Duration> {
+        ctx.time_remaining()
+    }
+}
+
+#[tokio::test]
+async fn call_timeout_with_max_duration() {
+    let addr = Sleeper.start();
+
+    let remaining = addr.call_timeout(Deadline, Duration::MAX).await.unwrap();
+
+    assert_eq!(remaining, None);
+}